@@ -1,8 +1,35 @@
+use std::cmp::Ordering;
 use std::fmt;
+use std::io::{Read, Write};
 use std::ops::Deref;
 
 use serde::de::Error as DeError;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+fn double_sha256(data: &[u8]) -> [u8; 32] {
+    let first = Sha256::digest(data);
+    let second = Sha256::digest(first);
+    second.into()
+}
+
+pub const SIGHASH_ALL: u32 = 0x01;
+pub const SIGHASH_NONE: u32 = 0x02;
+pub const SIGHASH_SINGLE: u32 = 0x03;
+pub const SIGHASH_ANYONECANPAY: u32 = 0x80;
+
+/// Streaming decode from a consensus-serialized byte stream, in the spirit
+/// of rust-bitcoin's `consensus::encode` module. Reads exactly as many bytes
+/// as the type needs, so it can decode directly from a `TcpStream` without
+/// buffering a whole message up front.
+pub trait Decodable: Sized {
+    fn consensus_decode<R: Read>(r: &mut R) -> Result<Self, BitcoinError>;
+}
+
+/// Streaming encode counterpart to [`Decodable`].
+pub trait Encodable {
+    fn consensus_encode<W: Write>(&self, w: &mut W) -> Result<usize, BitcoinError>;
+}
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub struct CompactSize {
@@ -13,6 +40,30 @@ pub struct CompactSize {
 pub enum BitcoinError {
     InsufficientBytes,
     InvalidFormat,
+    AmountOverflow,
+    InvalidProofOfWork,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+pub struct Amount(u64);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+
+    pub fn from_sat(satoshis: u64) -> Self {
+        Self(satoshis)
+    }
+
+    pub fn to_sat(self) -> u64 {
+        self.0
+    }
+
+    pub fn checked_add(self, other: Amount) -> Result<Amount, BitcoinError> {
+        self.0
+            .checked_add(other.0)
+            .map(Amount)
+            .ok_or(BitcoinError::AmountOverflow)
+    }
 }
 
 impl CompactSize {
@@ -21,6 +72,22 @@ impl CompactSize {
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.consensus_encode(&mut buf)
+            .expect("writing to a Vec never fails");
+        buf
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
+        let mut cursor = bytes;
+        let value = Self::consensus_decode(&mut cursor)?;
+        let consumed = bytes.len() - cursor.len();
+        Ok((value, consumed))
+    }
+}
+
+impl Encodable for CompactSize {
+    fn consensus_encode<W: Write>(&self, w: &mut W) -> Result<usize, BitcoinError> {
         let mut bytes = match self.value {
             ..=0xfc => Vec::with_capacity(1),
             0xfd..=0xffff => Vec::with_capacity(3),
@@ -44,28 +111,37 @@ impl CompactSize {
             },
         }
 
-        bytes
+        w.write_all(&bytes).map_err(|_| BitcoinError::InvalidFormat)?;
+        Ok(bytes.len())
     }
+}
 
-    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
-        match bytes {
-            [] => Err(BitcoinError::InsufficientBytes),
-            [uno @ ..=0xfc, ..] => Ok((Self::new(*uno as u64), 1)),
-            [0xfd, rest @ ..] => {
-                let slice = rest.get(..2).ok_or(BitcoinError::InsufficientBytes)?;
-                let value = u16::from_le_bytes(slice.try_into().unwrap()) as u64;
-                Ok((Self::new(value), 3))
+impl Decodable for CompactSize {
+    fn consensus_decode<R: Read>(r: &mut R) -> Result<Self, BitcoinError> {
+        let mut prefix = [0u8; 1];
+        r.read_exact(&mut prefix)
+            .map_err(|_| BitcoinError::InsufficientBytes)?;
+
+        match prefix[0] {
+            0xfd => {
+                let mut buf = [0u8; 2];
+                r.read_exact(&mut buf)
+                    .map_err(|_| BitcoinError::InsufficientBytes)?;
+                Ok(Self::new(u16::from_le_bytes(buf) as u64))
             },
-            [0xfe, rest @ ..] => {
-                let slice = rest.get(..4).ok_or(BitcoinError::InsufficientBytes)?;
-                let value = u32::from_le_bytes(slice.try_into().unwrap()) as u64;
-                Ok((Self::new(value), 5))
+            0xfe => {
+                let mut buf = [0u8; 4];
+                r.read_exact(&mut buf)
+                    .map_err(|_| BitcoinError::InsufficientBytes)?;
+                Ok(Self::new(u32::from_le_bytes(buf) as u64))
             },
-            [0xff, rest @ ..] => {
-                let slice = rest.get(..8).ok_or(BitcoinError::InsufficientBytes)?;
-                let value = u64::from_le_bytes(slice.try_into().unwrap());
-                Ok((Self::new(value), 9))
+            0xff => {
+                let mut buf = [0u8; 8];
+                r.read_exact(&mut buf)
+                    .map_err(|_| BitcoinError::InsufficientBytes)?;
+                Ok(Self::new(u64::from_le_bytes(buf)))
             },
+            small => Ok(Self::new(small as u64)),
         }
     }
 }
@@ -73,14 +149,23 @@ impl CompactSize {
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Txid(pub [u8; 32]);
 
+impl Txid {
+    /// Renders the txid byte-reversed, matching the convention used by block
+    /// explorers and `bitcoin-cli` (consensus txids are internally
+    /// little-endian but displayed as big-endian hex).
+    pub fn to_hex_reversed(&self) -> String {
+        let mut bytes = self.0;
+        bytes.reverse();
+        hex::encode(bytes)
+    }
+}
+
 impl Serialize for Txid {
     fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        // let mut bytes = self.0;
-        // bytes.reverse();
-        s.serialize_str(&hex::encode(self.0))
+        s.serialize_str(&self.to_hex_reversed())
     }
 }
 
@@ -94,14 +179,31 @@ impl<'de> Deserialize<'de> for Txid {
         let decoded =
             hex::decode(&hex_string).map_err(|_| DeError::custom("Invalid hex string"))?;
 
-        let bytes = decoded
+        let mut bytes: [u8; 32] = decoded
             .try_into()
             .map_err(|_| DeError::custom("Txid must be exactly 32 bytes"))?;
+        bytes.reverse();
 
         Ok(Txid(bytes))
     }
 }
 
+impl Encodable for Txid {
+    fn consensus_encode<W: Write>(&self, w: &mut W) -> Result<usize, BitcoinError> {
+        w.write_all(&self.0).map_err(|_| BitcoinError::InvalidFormat)?;
+        Ok(self.0.len())
+    }
+}
+
+impl Decodable for Txid {
+    fn consensus_decode<R: Read>(r: &mut R) -> Result<Self, BitcoinError> {
+        let mut bytes = [0u8; 32];
+        r.read_exact(&mut bytes)
+            .map_err(|_| BitcoinError::InsufficientBytes)?;
+        Ok(Txid(bytes))
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct OutPoint {
     pub txid: Txid,
@@ -117,19 +219,42 @@ impl OutPoint {
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(36);
-        bytes.extend_from_slice(&self.txid.0);
-        bytes.extend_from_slice(&self.vout.to_le_bytes());
-        bytes
+        let mut buf = Vec::new();
+        self.consensus_encode(&mut buf)
+            .expect("writing to a Vec never fails");
+        buf
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
-        if bytes.len() < 36 {
-            return Err(BitcoinError::InsufficientBytes);
-        }
-        let txid = (&bytes[..32]).try_into().unwrap();
-        let vout = u32::from_le_bytes((&bytes[32..36]).try_into().unwrap());
-        Ok((Self::new(txid, vout), 36))
+        let mut cursor = bytes;
+        let value = Self::consensus_decode(&mut cursor)?;
+        let consumed = bytes.len() - cursor.len();
+        Ok((value, consumed))
+    }
+}
+
+impl Encodable for OutPoint {
+    fn consensus_encode<W: Write>(&self, w: &mut W) -> Result<usize, BitcoinError> {
+        let mut n = self.txid.consensus_encode(w)?;
+        w.write_all(&self.vout.to_le_bytes())
+            .map_err(|_| BitcoinError::InvalidFormat)?;
+        n += 4;
+        Ok(n)
+    }
+}
+
+impl Decodable for OutPoint {
+    fn consensus_decode<R: Read>(r: &mut R) -> Result<Self, BitcoinError> {
+        let txid = Txid::consensus_decode(r)?;
+
+        let mut vout_bytes = [0u8; 4];
+        r.read_exact(&mut vout_bytes)
+            .map_err(|_| BitcoinError::InsufficientBytes)?;
+
+        Ok(OutPoint {
+            txid,
+            vout: u32::from_le_bytes(vout_bytes),
+        })
     }
 }
 
@@ -144,26 +269,39 @@ impl Script {
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
-        let len = CompactSize::new(self.bytes.len() as u64);
-        let len_bytes = len.to_bytes();
-
-        let mut result = Vec::with_capacity(len_bytes.len() + self.bytes.len());
-        result.extend_from_slice(&len_bytes);
-        result.extend_from_slice(&self.bytes);
-        result
+        let mut buf = Vec::new();
+        self.consensus_encode(&mut buf)
+            .expect("writing to a Vec never fails");
+        buf
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
-        let (len, len_bytes) = CompactSize::from_bytes(bytes)?;
-        let len_script = len.value as usize;
+        let mut cursor = bytes;
+        let value = Self::consensus_decode(&mut cursor)?;
+        let consumed = bytes.len() - cursor.len();
+        Ok((value, consumed))
+    }
+}
 
-        let total_needed = len_bytes + len_script;
-        if bytes.len() < total_needed {
-            return Err(BitcoinError::InsufficientBytes);
-        }
+impl Encodable for Script {
+    fn consensus_encode<W: Write>(&self, w: &mut W) -> Result<usize, BitcoinError> {
+        let mut n = CompactSize::new(self.bytes.len() as u64).consensus_encode(w)?;
+        w.write_all(&self.bytes)
+            .map_err(|_| BitcoinError::InvalidFormat)?;
+        n += self.bytes.len();
+        Ok(n)
+    }
+}
+
+impl Decodable for Script {
+    fn consensus_decode<R: Read>(r: &mut R) -> Result<Self, BitcoinError> {
+        let len = CompactSize::consensus_decode(r)?;
+
+        let mut bytes = vec![0u8; len.value as usize];
+        r.read_exact(&mut bytes)
+            .map_err(|_| BitcoinError::InsufficientBytes)?;
 
-        let script_bytes = bytes[len_bytes..total_needed].to_vec();
-        Ok((Script::new(script_bytes), total_needed))
+        Ok(Script::new(bytes))
     }
 }
 
@@ -175,62 +313,149 @@ impl Deref for Script {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct TransactionOutput {
+    pub value: Amount,
+    pub script_pubkey: Script,
+}
+
+impl TransactionOutput {
+    pub fn new(value: Amount, script_pubkey: Script) -> Self {
+        Self {
+            value,
+            script_pubkey,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let script_bytes = self.script_pubkey.to_bytes();
+
+        let mut bytes = Vec::with_capacity(8 + script_bytes.len());
+        bytes.extend_from_slice(&self.value.to_sat().to_le_bytes());
+        bytes.extend_from_slice(&script_bytes);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
+        if bytes.len() < 8 {
+            return Err(BitcoinError::InsufficientBytes);
+        }
+
+        let value = Amount::from_sat(u64::from_le_bytes((&bytes[..8]).try_into().unwrap()));
+        let (script_pubkey, script_len) = Script::from_bytes(&bytes[8..])?;
+
+        Ok((TransactionOutput::new(value, script_pubkey), 8 + script_len))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+pub struct Witness(pub Vec<Vec<u8>>);
+
+impl Witness {
+    pub fn new(items: Vec<Vec<u8>>) -> Self {
+        Self(items)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = CompactSize::new(self.0.len() as u64).to_bytes();
+
+        for item in &self.0 {
+            bytes.extend_from_slice(&CompactSize::new(item.len() as u64).to_bytes());
+            bytes.extend_from_slice(item);
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
+        let (item_count, mut cursor) = CompactSize::from_bytes(bytes)?;
+
+        let mut items = Vec::with_capacity(item_count.value as usize);
+
+        for _ in 0..item_count.value {
+            let (item_len, len_bytes) = CompactSize::from_bytes(&bytes[cursor..])?;
+            cursor += len_bytes;
+
+            let item_len = item_len.value as usize;
+            if bytes.len() < cursor + item_len {
+                return Err(BitcoinError::InsufficientBytes);
+            }
+
+            items.push(bytes[cursor..cursor + item_len].to_vec());
+            cursor += item_len;
+        }
+
+        Ok((Witness::new(items), cursor))
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct TransactionInput {
     pub previous_output: OutPoint,
     pub script_sig: Script,
     pub sequence: u32,
+    pub witness: Witness,
 }
 
 impl TransactionInput {
-    pub fn new(previous_output: OutPoint, script_sig: Script, sequence: u32) -> Self {
+    pub fn new(
+        previous_output: OutPoint,
+        script_sig: Script,
+        sequence: u32,
+        witness: Witness,
+    ) -> Self {
         Self {
             previous_output,
             script_sig,
             sequence,
+            witness,
         }
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
-        let outpoint_bytes = self.previous_output.to_bytes();
-        let script_bytes = self.script_sig.to_bytes();
-        let sequence_bytes: [u8; 4] = self.sequence.to_le_bytes();
-
-        let mut bytes = Vec::with_capacity(
-            outpoint_bytes.len() + script_bytes.len() + sequence_bytes.len(),
-        );
-
-        bytes.extend_from_slice(&outpoint_bytes);
-        bytes.extend_from_slice(&script_bytes);
-        bytes.extend_from_slice(&sequence_bytes);
-        bytes
+        let mut buf = Vec::new();
+        self.consensus_encode(&mut buf)
+            .expect("writing to a Vec never fails");
+        buf
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
-        let mut cursor = 0;
-
-        let (previous_output, outpoint_len) = OutPoint::from_bytes(&bytes[cursor..])?;
-        cursor += outpoint_len;
-
-        let (script_sig, script_len) = Script::from_bytes(&bytes[cursor..])?;
-        cursor += script_len;
+        let mut cursor = bytes;
+        let value = Self::consensus_decode(&mut cursor)?;
+        let consumed = bytes.len() - cursor.len();
+        Ok((value, consumed))
+    }
+}
 
-        if bytes.len() < cursor + 4 {
-            return Err(BitcoinError::InsufficientBytes);
-        }
+impl Encodable for TransactionInput {
+    fn consensus_encode<W: Write>(&self, w: &mut W) -> Result<usize, BitcoinError> {
+        let mut n = self.previous_output.consensus_encode(w)?;
+        n += self.script_sig.consensus_encode(w)?;
+        w.write_all(&self.sequence.to_le_bytes())
+            .map_err(|_| BitcoinError::InvalidFormat)?;
+        n += 4;
+        Ok(n)
+    }
+}
 
-        let sequence = u32::from_le_bytes([
-            bytes[cursor],
-            bytes[cursor + 1],
-            bytes[cursor + 2],
-            bytes[cursor + 3],
-        ]);
+impl Decodable for TransactionInput {
+    fn consensus_decode<R: Read>(r: &mut R) -> Result<Self, BitcoinError> {
+        let previous_output = OutPoint::consensus_decode(r)?;
+        let script_sig = Script::consensus_decode(r)?;
 
-        cursor += 4;
+        let mut sequence_bytes = [0u8; 4];
+        r.read_exact(&mut sequence_bytes)
+            .map_err(|_| BitcoinError::InsufficientBytes)?;
 
-        Ok((
-            TransactionInput::new(previous_output, script_sig, sequence),
-            cursor,
+        Ok(TransactionInput::new(
+            previous_output,
+            script_sig,
+            u32::from_le_bytes(sequence_bytes),
+            Witness::default(),
         ))
     }
 }
@@ -239,24 +464,40 @@ impl TransactionInput {
 pub struct BitcoinTransaction {
     pub version: u32,
     pub inputs: Vec<TransactionInput>,
+    pub outputs: Vec<TransactionOutput>,
     pub lock_time: u32,
 }
 
 impl BitcoinTransaction {
-    pub fn new(version: u32, inputs: Vec<TransactionInput>, lock_time: u32) -> Self {
+    pub fn new(
+        version: u32,
+        inputs: Vec<TransactionInput>,
+        outputs: Vec<TransactionOutput>,
+        lock_time: u32,
+    ) -> Self {
         Self {
             version,
             inputs,
+            outputs,
             lock_time,
         }
     }
 
-    pub fn to_bytes(&self) -> Vec<u8> {
-        let input_count = CompactSize::new(self.inputs.len() as u64);
-        let input_count_bytes = input_count.to_bytes();
+    /// Whether this transaction carries a BIP141 witness and must be
+    /// serialized in the extended SegWit format.
+    fn has_witness(&self) -> bool {
+        self.inputs.iter().any(|input| !input.witness.is_empty())
+    }
 
-        // initial capacity = version + count + lock_time
-        let mut bytes = Vec::with_capacity(4 + input_count_bytes.len() + 4);
+    /// Serializes the transaction in the legacy (pre-SegWit) format, with no
+    /// marker/flag or witness data. This is what txid hashing signs over.
+    fn to_bytes_legacy(&self) -> Vec<u8> {
+        let input_count_bytes = CompactSize::new(self.inputs.len() as u64).to_bytes();
+        let output_count_bytes = CompactSize::new(self.outputs.len() as u64).to_bytes();
+
+        // initial capacity = version + input count + output count + lock_time
+        let mut bytes =
+            Vec::with_capacity(4 + input_count_bytes.len() + output_count_bytes.len() + 4);
 
         bytes.extend_from_slice(&self.version.to_le_bytes());
         bytes.extend_from_slice(&input_count_bytes);
@@ -265,48 +506,230 @@ impl BitcoinTransaction {
             bytes.extend_from_slice(&input.to_bytes());
         }
 
+        bytes.extend_from_slice(&output_count_bytes);
+
+        for output in &self.outputs {
+            bytes.extend_from_slice(&output.to_bytes());
+        }
+
         bytes.extend_from_slice(&self.lock_time.to_le_bytes());
 
         bytes
     }
 
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.consensus_encode(&mut buf)
+            .expect("writing to a Vec never fails");
+        buf
+    }
+
+    /// The transaction's identifier: the double-SHA256 of its legacy
+    /// (non-witness) serialization. Stable across witness malleability.
+    pub fn txid(&self) -> Txid {
+        Txid(double_sha256(&self.to_bytes_legacy()))
+    }
+
+    /// The transaction's witness identifier: the double-SHA256 of its full
+    /// serialization, including the marker/flag and witness data when present.
+    pub fn wtxid(&self) -> Txid {
+        Txid(double_sha256(&self.to_bytes()))
+    }
+
     pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
-        let mut cursor = 0;
+        let mut cursor = bytes;
+        let value = Self::consensus_decode(&mut cursor)?;
+        let consumed = bytes.len() - cursor.len();
+        Ok((value, consumed))
+    }
 
-        if bytes.len() < 4 {
-            return Err(BitcoinError::InsufficientBytes);
+    /// Computes the BIP143 SegWit signature hash for `input_index`, signing
+    /// over `value` (the amount of the output being spent) and `script_code`
+    /// (the scriptPubKey, or the redeem script for P2SH-wrapped SegWit).
+    pub fn sighash_bip143(
+        &self,
+        input_index: usize,
+        script_code: &Script,
+        value: u64,
+        sighash_type: u32,
+    ) -> [u8; 32] {
+        let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+        let base_type = sighash_type & !SIGHASH_ANYONECANPAY;
+
+        let hash_prevouts = if anyone_can_pay {
+            [0u8; 32]
+        } else {
+            let mut buf = Vec::new();
+            for input in &self.inputs {
+                buf.extend_from_slice(&input.previous_output.to_bytes());
+            }
+            double_sha256(&buf)
+        };
+
+        let hash_sequence = if anyone_can_pay
+            || base_type == SIGHASH_SINGLE
+            || base_type == SIGHASH_NONE
+        {
+            [0u8; 32]
+        } else {
+            let mut buf = Vec::new();
+            for input in &self.inputs {
+                buf.extend_from_slice(&input.sequence.to_le_bytes());
+            }
+            double_sha256(&buf)
+        };
+
+        let hash_outputs = if base_type == SIGHASH_NONE {
+            [0u8; 32]
+        } else if base_type == SIGHASH_SINGLE {
+            match self.outputs.get(input_index) {
+                Some(output) => double_sha256(&output.to_bytes()),
+                None => [0u8; 32],
+            }
+        } else {
+            let mut buf = Vec::new();
+            for output in &self.outputs {
+                buf.extend_from_slice(&output.to_bytes());
+            }
+            double_sha256(&buf)
+        };
+
+        let input = &self.inputs[input_index];
+
+        let mut preimage = Vec::new();
+        preimage.extend_from_slice(&self.version.to_le_bytes());
+        preimage.extend_from_slice(&hash_prevouts);
+        preimage.extend_from_slice(&hash_sequence);
+        preimage.extend_from_slice(&input.previous_output.to_bytes());
+        preimage.extend_from_slice(&script_code.to_bytes());
+        preimage.extend_from_slice(&value.to_le_bytes());
+        preimage.extend_from_slice(&input.sequence.to_le_bytes());
+        preimage.extend_from_slice(&hash_outputs);
+        preimage.extend_from_slice(&self.lock_time.to_le_bytes());
+        preimage.extend_from_slice(&sighash_type.to_le_bytes());
+
+        double_sha256(&preimage)
+    }
+}
+
+impl Encodable for BitcoinTransaction {
+    fn consensus_encode<W: Write>(&self, w: &mut W) -> Result<usize, BitcoinError> {
+        let is_witness = self.has_witness();
+        let mut n = 0;
+
+        w.write_all(&self.version.to_le_bytes())
+            .map_err(|_| BitcoinError::InvalidFormat)?;
+        n += 4;
+
+        if is_witness {
+            w.write_all(&[0x00, 0x01])
+                .map_err(|_| BitcoinError::InvalidFormat)?;
+            n += 2;
         }
 
-        let version = u32::from_le_bytes((&bytes[0..4]).try_into().unwrap());
-        cursor += 4;
+        n += CompactSize::new(self.inputs.len() as u64).consensus_encode(w)?;
+        for input in &self.inputs {
+            n += input.consensus_encode(w)?;
+        }
 
-        let (input_count, count_len) = CompactSize::from_bytes(&bytes[cursor..])?;
-        cursor += count_len;
+        n += CompactSize::new(self.outputs.len() as u64).consensus_encode(w)?;
+        for output in &self.outputs {
+            let bytes = output.to_bytes();
+            w.write_all(&bytes).map_err(|_| BitcoinError::InvalidFormat)?;
+            n += bytes.len();
+        }
 
-        let mut inputs = Vec::with_capacity(input_count.value as usize);
+        if is_witness {
+            for input in &self.inputs {
+                let bytes = input.witness.to_bytes();
+                w.write_all(&bytes).map_err(|_| BitcoinError::InvalidFormat)?;
+                n += bytes.len();
+            }
+        }
+
+        w.write_all(&self.lock_time.to_le_bytes())
+            .map_err(|_| BitcoinError::InvalidFormat)?;
+        n += 4;
+
+        Ok(n)
+    }
+}
 
+impl Decodable for BitcoinTransaction {
+    fn consensus_decode<R: Read>(r: &mut R) -> Result<Self, BitcoinError> {
+        let mut version_bytes = [0u8; 4];
+        r.read_exact(&mut version_bytes)
+            .map_err(|_| BitcoinError::InsufficientBytes)?;
+        let version = u32::from_le_bytes(version_bytes);
+
+        let mut marker = [0u8; 1];
+        r.read_exact(&mut marker)
+            .map_err(|_| BitcoinError::InsufficientBytes)?;
+
+        let is_witness = marker[0] == 0x00;
+
+        let input_count = if is_witness {
+            let mut flag = [0u8; 1];
+            r.read_exact(&mut flag)
+                .map_err(|_| BitcoinError::InsufficientBytes)?;
+            if flag[0] != 0x01 {
+                return Err(BitcoinError::InvalidFormat);
+            }
+            CompactSize::consensus_decode(r)?
+        } else {
+            // The marker byte we already consumed is actually the first byte
+            // of the input CompactSize; feed it back in ahead of the rest of
+            // the stream instead of trying to "un-read" it.
+            let mut chained = (&marker[..]).chain(&mut *r);
+            CompactSize::consensus_decode(&mut chained)?
+        };
+
+        let mut inputs = Vec::with_capacity(input_count.value as usize);
         for _ in 0..input_count.value {
-            let (input, input_len) = TransactionInput::from_bytes(&bytes[cursor..])?;
-            inputs.push(input);
-            cursor += input_len;
+            inputs.push(TransactionInput::consensus_decode(r)?);
         }
 
-        if bytes.len() < cursor + 4 {
-            return Err(BitcoinError::InsufficientBytes);
+        let output_count = CompactSize::consensus_decode(r)?;
+        let mut outputs = Vec::with_capacity(output_count.value as usize);
+        for _ in 0..output_count.value {
+            let mut value_bytes = [0u8; 8];
+            r.read_exact(&mut value_bytes)
+                .map_err(|_| BitcoinError::InsufficientBytes)?;
+            let value = Amount::from_sat(u64::from_le_bytes(value_bytes));
+            let script_pubkey = Script::consensus_decode(r)?;
+            outputs.push(TransactionOutput::new(value, script_pubkey));
+        }
+
+        if is_witness {
+            for input in &mut inputs {
+                input.witness = decode_witness(r)?;
+            }
         }
 
-        let lock_time = u32::from_le_bytes([
-            bytes[cursor],
-            bytes[cursor + 1],
-            bytes[cursor + 2],
-            bytes[cursor + 3],
-        ]);
-        cursor += 4;
+        let mut lock_time_bytes = [0u8; 4];
+        r.read_exact(&mut lock_time_bytes)
+            .map_err(|_| BitcoinError::InsufficientBytes)?;
+        let lock_time = u32::from_le_bytes(lock_time_bytes);
 
-        Ok((BitcoinTransaction::new(version, inputs, lock_time), cursor))
+        Ok(BitcoinTransaction::new(version, inputs, outputs, lock_time))
     }
 }
 
+fn decode_witness<R: Read>(r: &mut R) -> Result<Witness, BitcoinError> {
+    let item_count = CompactSize::consensus_decode(r)?;
+
+    let mut items = Vec::with_capacity(item_count.value as usize);
+    for _ in 0..item_count.value {
+        let item_len = CompactSize::consensus_decode(r)?;
+        let mut item = vec![0u8; item_len.value as usize];
+        r.read_exact(&mut item)
+            .map_err(|_| BitcoinError::InsufficientBytes)?;
+        items.push(item);
+    }
+
+    Ok(Witness::new(items))
+}
+
 impl fmt::Display for BitcoinTransaction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -333,6 +756,189 @@ impl fmt::Display for BitcoinTransaction {
             write!(f, "\n  Sequence: 0x{:08x}\n", input.sequence)?;
         }
 
+        writeln!(f, " Outputs ({}):", self.outputs.len())?;
+
+        for (i, output) in self.outputs.iter().enumerate() {
+            write!(
+                f,
+                "  Output: {}:::\nValue: {}\nScript Pubkey: ",
+                i,
+                output.value.to_sat()
+            )?;
+
+            for &byte in &output.script_pubkey.bytes {
+                write!(f, "{byte:02x}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A 256-bit unsigned integer stored as four 64-bit limbs, least significant
+/// limb first. Only the operations needed for compact-`bits` target decoding
+/// and proof-of-work comparison are implemented.
+#[derive(Debug, Clone, Copy)]
+pub struct Uint256([u64; 4]);
+
+impl Uint256 {
+    pub const ZERO: Uint256 = Uint256([0; 4]);
+
+    pub fn from_u64(value: u64) -> Self {
+        Self([value, 0, 0, 0])
+    }
+
+    /// Interprets `bytes` as a little-endian 256-bit integer.
+    pub fn from_le_bytes(bytes: [u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            *limb = u64::from_le_bytes(bytes[i * 8..(i + 1) * 8].try_into().unwrap());
+        }
+        Self(limbs)
+    }
+
+}
+
+impl std::ops::Shl<u32> for Uint256 {
+    type Output = Uint256;
+
+    fn shl(self, shift: u32) -> Self {
+        if shift == 0 {
+            return self;
+        }
+        if shift >= 256 {
+            return Uint256::ZERO;
+        }
+
+        let limb_shift = (shift / 64) as usize;
+        let bit_shift = shift % 64;
+
+        let mut out = [0u64; 4];
+        for i in (limb_shift..4).rev() {
+            let src = i - limb_shift;
+            let mut value = self.0[src] << bit_shift;
+            if bit_shift > 0 && src > 0 {
+                value |= self.0[src - 1] >> (64 - bit_shift);
+            }
+            out[i] = value;
+        }
+
+        Uint256(out)
+    }
+}
+
+impl PartialEq for Uint256 {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Uint256 {}
+
+impl PartialOrd for Uint256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Uint256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        for i in (0..4).rev() {
+            match self.0[i].cmp(&other.0[i]) {
+                Ordering::Equal => continue,
+                ordering => return ordering,
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct BlockHeader {
+    pub version: u32,
+    pub prev_blockhash: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub time: u32,
+    pub bits: u32,
+    pub nonce: u32,
+}
+
+impl BlockHeader {
+    pub fn new(
+        version: u32,
+        prev_blockhash: [u8; 32],
+        merkle_root: [u8; 32],
+        time: u32,
+        bits: u32,
+        nonce: u32,
+    ) -> Self {
+        Self {
+            version,
+            prev_blockhash,
+            merkle_root,
+            time,
+            bits,
+            nonce,
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 80] {
+        let mut bytes = [0u8; 80];
+        bytes[0..4].copy_from_slice(&self.version.to_le_bytes());
+        bytes[4..36].copy_from_slice(&self.prev_blockhash);
+        bytes[36..68].copy_from_slice(&self.merkle_root);
+        bytes[68..72].copy_from_slice(&self.time.to_le_bytes());
+        bytes[72..76].copy_from_slice(&self.bits.to_le_bytes());
+        bytes[76..80].copy_from_slice(&self.nonce.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
+        if bytes.len() < 80 {
+            return Err(BitcoinError::InsufficientBytes);
+        }
+
+        let version = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
+        let prev_blockhash = bytes[4..36].try_into().unwrap();
+        let merkle_root = bytes[36..68].try_into().unwrap();
+        let time = u32::from_le_bytes(bytes[68..72].try_into().unwrap());
+        let bits = u32::from_le_bytes(bytes[72..76].try_into().unwrap());
+        let nonce = u32::from_le_bytes(bytes[76..80].try_into().unwrap());
+
+        Ok((
+            Self::new(version, prev_blockhash, merkle_root, time, bits, nonce),
+            80,
+        ))
+    }
+
+    /// Decodes the compact `bits` field into a full 256-bit target.
+    pub fn target(&self) -> Uint256 {
+        let exponent = self.bits >> 24;
+        let mantissa = (self.bits & 0x00ff_ffff) as u64;
+
+        if mantissa > 0x007f_ffff {
+            return Uint256::ZERO;
+        }
+
+        if exponent < 3 {
+            return Uint256::from_u64(mantissa >> (8 * (3 - exponent)));
+        }
+
+        Uint256::from_u64(mantissa) << (8 * (exponent - 3))
+    }
+
+    /// Performs an SPV-style proof-of-work check: the header's double-SHA256
+    /// hash, read as a little-endian 256-bit integer, must not exceed the
+    /// target implied by `bits`.
+    pub fn spv_validate(&self) -> Result<(), BitcoinError> {
+        let hash = Uint256::from_le_bytes(double_sha256(&self.to_bytes()));
+
+        if hash > self.target() {
+            return Err(BitcoinError::InvalidProofOfWork);
+        }
+
         Ok(())
     }
 }
@@ -58,7 +58,8 @@ mod tests {
     fn test_tx_input_roundtrip() {
         let outpoint = OutPoint::new(dummy_txid(1), 0);
         let script = Script::new(vec![0x01, 0x02]);
-        let input = TransactionInput::new(outpoint.clone(), script.clone(), 0xffffffff);
+        let input =
+            TransactionInput::new(outpoint.clone(), script.clone(), 0xffffffff, Witness::default());
         let bytes = input.to_bytes();
         let (parsed, consumed) = TransactionInput::from_bytes(&bytes).unwrap();
         assert_eq!(parsed, input);
@@ -71,8 +72,13 @@ mod tests {
             OutPoint::new(dummy_txid(1), 0),
             Script::new(vec![0x01, 0x02]),
             0xffffffff,
+            Witness::default(),
         )];
-        let tx = BitcoinTransaction::new(2, inputs.clone(), 1000);
+        let outputs = vec![TransactionOutput::new(
+            Amount::from_sat(5000),
+            Script::new(vec![0x76, 0xa9]),
+        )];
+        let tx = BitcoinTransaction::new(2, inputs.clone(), outputs.clone(), 1000);
         let bytes = tx.to_bytes();
         let (parsed, consumed) = BitcoinTransaction::from_bytes(&bytes).unwrap();
         assert_eq!(parsed, tx);
@@ -85,8 +91,10 @@ mod tests {
             OutPoint::new(dummy_txid(0xab), 3),
             Script::new(vec![0xde, 0xad, 0xbe, 0xef]),
             0xabcdef01,
+            Witness::default(),
         );
-        let tx = BitcoinTransaction::new(1, vec![input], 999);
+        let output = TransactionOutput::new(Amount::from_sat(1000), Script::new(vec![0xac]));
+        let tx = BitcoinTransaction::new(1, vec![input], vec![output], 999);
 
         let json = serde_json::to_string_pretty(&tx).unwrap();
         let parsed: BitcoinTransaction = serde_json::from_str(&json).unwrap();
@@ -102,11 +110,278 @@ mod tests {
             OutPoint::new(dummy_txid(0xcd), 7),
             Script::new(vec![0x01, 0x02, 0x03]),
             0xffffffff,
+            Witness::default(),
         );
-        let tx = BitcoinTransaction::new(1, vec![input], 0);
+        let tx_output = TransactionOutput::new(Amount::from_sat(42), Script::new(vec![0xab]));
+        let tx = BitcoinTransaction::new(1, vec![input], vec![tx_output], 0);
         let output = format!("{tx}");
         assert!(output.contains("Version: 1"));
         assert!(output.contains("Lock Time: 0"));
         assert!(output.contains("Previous Output Vout: 7"));
+        assert!(output.contains("Value: 42"));
+    }
+
+    #[test]
+    fn test_transaction_output_roundtrip() {
+        let output = TransactionOutput::new(
+            Amount::from_sat(100_000),
+            Script::new(vec![0x76, 0xa9, 0x14, 0x88, 0xac]),
+        );
+        let bytes = output.to_bytes();
+        let (parsed, consumed) = TransactionOutput::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, output);
+        assert_eq!(consumed, bytes.len());
+    }
+
+    #[test]
+    fn test_amount_checked_add() {
+        let a = Amount::from_sat(u64::MAX);
+        let b = Amount::from_sat(1);
+        assert_eq!(a.checked_add(b), Err(BitcoinError::AmountOverflow));
+
+        let c = Amount::from_sat(5);
+        let d = Amount::from_sat(10);
+        assert_eq!(c.checked_add(d), Ok(Amount::from_sat(15)));
+    }
+
+    #[test]
+    fn test_legacy_tx_roundtrip() {
+        let input = TransactionInput::new(
+            OutPoint::new(dummy_txid(1), 0),
+            Script::new(vec![0x01, 0x02]),
+            0xffffffff,
+            Witness::default(),
+        );
+        let output = TransactionOutput::new(Amount::from_sat(5000), Script::new(vec![0x76, 0xa9]));
+        let tx = BitcoinTransaction::new(1, vec![input], vec![output], 0);
+
+        let bytes = tx.to_bytes();
+        assert_ne!(bytes[4], 0x00, "legacy tx must not carry a segwit marker byte");
+
+        let (parsed, consumed) = BitcoinTransaction::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, tx);
+        assert_eq!(consumed, bytes.len());
+    }
+
+    #[test]
+    fn test_segwit_tx_roundtrip() {
+        let input = TransactionInput::new(
+            OutPoint::new(dummy_txid(2), 1),
+            Script::new(vec![]),
+            0xffffffff,
+            Witness::new(vec![vec![0x30, 0x44], vec![0x02, 0x01]]),
+        );
+        let output = TransactionOutput::new(Amount::from_sat(2500), Script::new(vec![0xac]));
+        let tx = BitcoinTransaction::new(2, vec![input], vec![output], 500);
+
+        let bytes = tx.to_bytes();
+        assert_eq!(bytes[4], 0x00, "segwit tx must carry the marker byte");
+        assert_eq!(bytes[5], 0x01, "segwit tx must carry the flag byte");
+
+        let (parsed, consumed) = BitcoinTransaction::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, tx);
+        assert_eq!(consumed, bytes.len());
+    }
+
+    #[test]
+    fn test_block_header_roundtrip() {
+        let header = BlockHeader::new(
+            1,
+            dummy_txid(0xaa),
+            dummy_txid(0xbb),
+            1_700_000_000,
+            0x1d00ffff,
+            42,
+        );
+        let bytes = header.to_bytes();
+        assert_eq!(bytes.len(), 80);
+
+        let (parsed, consumed) = BlockHeader::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, header);
+        assert_eq!(consumed, 80);
+    }
+
+    #[test]
+    fn test_block_header_target_decoding() {
+        // bits = 0x1d00ffff is the Bitcoin genesis block's difficulty-1 target.
+        let header = BlockHeader::new(1, dummy_txid(0), dummy_txid(0), 0, 0x1d00ffff, 0);
+        let target = header.target();
+        assert_eq!(target, Uint256::from_u64(0x00ffff) << (8 * (0x1d - 3)));
+
+        // a mantissa with its sign bit set decodes to a zero target.
+        let negative = BlockHeader::new(1, dummy_txid(0), dummy_txid(0), 0, 0x01800000, 0);
+        assert_eq!(negative.target(), Uint256::ZERO);
+    }
+
+    #[test]
+    fn test_block_header_spv_validate() {
+        // bits = 0 decodes to a zero target, so no hash can satisfy it.
+        let header = BlockHeader::new(1, dummy_txid(0), dummy_txid(0), 0, 0, 0);
+        assert_eq!(header.spv_validate(), Err(BitcoinError::InvalidProofOfWork));
+    }
+
+    #[test]
+    fn test_txid_excludes_witness_data() {
+        let input = TransactionInput::new(
+            OutPoint::new(dummy_txid(1), 0),
+            Script::new(vec![0x01, 0x02]),
+            0xffffffff,
+            Witness::default(),
+        );
+        let output = TransactionOutput::new(Amount::from_sat(5000), Script::new(vec![0x76, 0xa9]));
+
+        let legacy_tx = BitcoinTransaction::new(1, vec![input.clone()], vec![output.clone()], 0);
+
+        let witness_input = TransactionInput::new(
+            input.previous_output.clone(),
+            input.script_sig.clone(),
+            input.sequence,
+            Witness::new(vec![vec![0xde, 0xad]]),
+        );
+        let witness_tx = BitcoinTransaction::new(1, vec![witness_input], vec![output], 0);
+
+        // Adding a witness must not change the txid, only the wtxid.
+        assert_eq!(legacy_tx.txid(), witness_tx.txid());
+        assert_ne!(legacy_tx.wtxid(), witness_tx.wtxid());
+    }
+
+    #[test]
+    fn test_txid_hex_reversed() {
+        let mut raw = [0u8; 32];
+        raw[0] = 0xaa;
+        raw[31] = 0xbb;
+        let txid = Txid(raw);
+
+        let mut expected = raw;
+        expected.reverse();
+        assert_eq!(txid.to_hex_reversed(), hex::encode(expected));
+    }
+
+    #[test]
+    fn test_txid_json_roundtrip_preserves_bytes() {
+        let mut raw = [0u8; 32];
+        raw[0] = 0x01;
+        raw[31] = 0x02;
+        let txid = Txid(raw);
+
+        let json = serde_json::to_string(&txid).unwrap();
+        let parsed: Txid = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, txid);
+    }
+
+    #[test]
+    fn test_consensus_decode_from_stream() {
+        let input = TransactionInput::new(
+            OutPoint::new(dummy_txid(2), 1),
+            Script::new(vec![]),
+            0xffffffff,
+            Witness::new(vec![vec![0x30, 0x44], vec![0x02, 0x01]]),
+        );
+        let output = TransactionOutput::new(Amount::from_sat(2500), Script::new(vec![0xac]));
+        let tx = BitcoinTransaction::new(2, vec![input], vec![output], 500);
+
+        let bytes = tx.to_bytes();
+
+        // Decode directly from a `Read` stream, the way a `TcpStream` would be.
+        let mut stream = &bytes[..];
+        let decoded = BitcoinTransaction::consensus_decode(&mut stream).unwrap();
+        assert_eq!(decoded, tx);
+        assert!(stream.is_empty(), "consensus_decode must consume exactly one transaction");
+    }
+
+    #[test]
+    fn test_consensus_encode_matches_to_bytes() {
+        let input = TransactionInput::new(
+            OutPoint::new(dummy_txid(1), 0),
+            Script::new(vec![0x01, 0x02]),
+            0xffffffff,
+            Witness::default(),
+        );
+        let output = TransactionOutput::new(Amount::from_sat(5000), Script::new(vec![0x76, 0xa9]));
+        let tx = BitcoinTransaction::new(1, vec![input], vec![output], 0);
+
+        let mut buf = Vec::new();
+        let written = tx.consensus_encode(&mut buf).unwrap();
+        assert_eq!(buf, tx.to_bytes());
+        assert_eq!(written, buf.len());
+    }
+
+    fn two_input_tx(second_sequence: u32) -> BitcoinTransaction {
+        let inputs = vec![
+            TransactionInput::new(
+                OutPoint::new(dummy_txid(1), 0),
+                Script::new(vec![]),
+                0xffffffff,
+                Witness::default(),
+            ),
+            TransactionInput::new(
+                OutPoint::new(dummy_txid(2), 1),
+                Script::new(vec![]),
+                second_sequence,
+                Witness::default(),
+            ),
+        ];
+        let outputs = vec![
+            TransactionOutput::new(Amount::from_sat(1000), Script::new(vec![0x01])),
+            TransactionOutput::new(Amount::from_sat(2000), Script::new(vec![0x02])),
+        ];
+        BitcoinTransaction::new(2, inputs, outputs, 0)
+    }
+
+    #[test]
+    fn test_sighash_bip143_differs_by_sighash_type() {
+        let tx = two_input_tx(0xffffffff);
+        let script_code = Script::new(vec![0x76, 0xa9, 0x14, 0x88, 0xac]);
+
+        let all = tx.sighash_bip143(0, &script_code, 5000, SIGHASH_ALL);
+        let none = tx.sighash_bip143(0, &script_code, 5000, SIGHASH_NONE);
+        let single = tx.sighash_bip143(0, &script_code, 5000, SIGHASH_SINGLE);
+
+        assert_ne!(all, none);
+        assert_ne!(all, single);
+        assert_ne!(none, single);
+    }
+
+    #[test]
+    fn test_sighash_bip143_anyonecanpay_ignores_other_inputs() {
+        let script_code = Script::new(vec![0x76, 0xa9, 0x14, 0x88, 0xac]);
+        let sighash_type = SIGHASH_ALL | SIGHASH_ANYONECANPAY;
+
+        let tx_a = two_input_tx(0xffffffff);
+        let tx_b = two_input_tx(0x00000000);
+
+        // changing the other input's sequence must not affect this input's
+        // ANYONECANPAY sighash...
+        assert_eq!(
+            tx_a.sighash_bip143(0, &script_code, 5000, sighash_type),
+            tx_b.sighash_bip143(0, &script_code, 5000, sighash_type)
+        );
+
+        // ...but must affect a plain SIGHASH_ALL sighash.
+        assert_ne!(
+            tx_a.sighash_bip143(0, &script_code, 5000, SIGHASH_ALL),
+            tx_b.sighash_bip143(0, &script_code, 5000, SIGHASH_ALL)
+        );
+    }
+
+    #[test]
+    fn test_sighash_bip143_single_ignores_other_outputs() {
+        let script_code = Script::new(vec![0x76, 0xa9, 0x14, 0x88, 0xac]);
+
+        let tx_a = two_input_tx(0xffffffff);
+        let mut tx_b = two_input_tx(0xffffffff);
+        tx_b.outputs[1] = TransactionOutput::new(Amount::from_sat(9999), Script::new(vec![0xff]));
+
+        // SIGHASH_SINGLE for input 0 only commits to output 0...
+        assert_eq!(
+            tx_a.sighash_bip143(0, &script_code, 5000, SIGHASH_SINGLE),
+            tx_b.sighash_bip143(0, &script_code, 5000, SIGHASH_SINGLE)
+        );
+
+        // ...but SIGHASH_ALL commits to every output.
+        assert_ne!(
+            tx_a.sighash_bip143(0, &script_code, 5000, SIGHASH_ALL),
+            tx_b.sighash_bip143(0, &script_code, 5000, SIGHASH_ALL)
+        );
     }
 }